@@ -1,24 +1,46 @@
-use std::{cell::RefCell, fmt::Debug, rc::Rc};
+use std::{collections::VecDeque, fmt::Debug};
 
-type Link<T, U> = Rc<RefCell<Node<T, U>>>;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Default number of phantom visits a thread temporarily charges to a leaf's
+/// path so concurrent selections diverge to different branches.
+const DEFAULT_VIRTUAL_LOSS: u32 = 1;
+
+/// Index of a node inside the [`Tree`] arena.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct NodeId(usize);
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct NodeStats {
     visits: u32,
     value: f32,
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct Node<T, U> {
-    parent: Option<Link<T, U>>,
-    children: Vec<Link<T, U>>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
     stats: NodeStats,
     state: T,
     action: Option<U>,
+    /// Prior weight assigned to the edge leading to this node, used by
+    /// prior-aware policies such as PUCT. Uniform unless the game overrides
+    /// [`GameState::action_priors`].
+    prior: f32,
+    /// `(action, prior)` pairs not yet turned into children, consumed lazily by
+    /// progressive widening. Empty once every action has been expanded.
+    unexpanded: Vec<(U, f32)>,
 }
 
 impl<T, U> Node<T, U> {
-    fn new(state: T, action: Option<U>) -> Link<T, U> {
-        Rc::new(RefCell::new(Self {
+    fn new(state: T, action: Option<U>, prior: f32) -> Self {
+        Self {
             parent: None,
             children: Vec::new(),
             stats: NodeStats {
@@ -26,154 +48,609 @@ impl<T, U> Node<T, U> {
                 value: 0.0,
             },
             state,
-            action: action,
-        }))
-    }
-}
-
-impl<T, U> Debug for Node<T, U>
-where
-    T: Debug,
-    U: Debug,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Node")
-            .field("children", &self.children)
-            .field("stats", &self.stats)
-            .field("state", &self.state)
-            .field("action", &self.action)
-            .finish()
+            action,
+            prior,
+            unexpanded: Vec::new(),
+        }
     }
 }
 
+/// A search tree stored as a flat arena of [`Node`]s.
+///
+/// Nodes reference their parent and children by [`NodeId`] rather than by
+/// `Rc`, so the whole tree is owned by `nodes` and drops in one shot with no
+/// reference cycles to leak.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct Tree<T, U> {
-    root: Link<T, U>,
+    nodes: Vec<Node<T, U>>,
+    root: NodeId,
 }
 
 impl<T, U> Tree<T, U> {
     fn new(state: T) -> Self {
         Self {
-            root: Node::new(state, None),
+            nodes: vec![Node::new(state, None, 1.0)],
+            root: NodeId(0),
         }
     }
 
-    fn add_child(&mut self, node: &Link<T, U>, state: T, action: U) {
-        let new_node = Node::new(state, Some(action));
-        new_node.borrow_mut().parent = Some(node.clone());
-        node.borrow_mut().children.push(new_node);
+    fn node(&self, id: NodeId) -> &Node<T, U> {
+        &self.nodes[id.0]
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<T, U> {
+        &mut self.nodes[id.0]
+    }
+
+    fn add_child(&mut self, parent: NodeId, state: T, action: U, prior: f32) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        let mut node = Node::new(state, Some(action), prior);
+        node.parent = Some(parent);
+        self.nodes.push(node);
+        self.node_mut(parent).children.push(id);
+        id
+    }
+
+    /// Keep only the subtree rooted at `new_root`, compacting it to the front
+    /// of the arena and discarding every other node. The new root's parent is
+    /// cleared and all surviving ids are remapped; accumulated stats are kept
+    /// untouched.
+    fn reroot(&mut self, new_root: NodeId) {
+        let mut old: Vec<Option<Node<T, U>>> =
+            std::mem::take(&mut self.nodes).into_iter().map(Some).collect();
+
+        // BFS from the new root to fix a new id for every surviving node.
+        let mut remap = vec![usize::MAX; old.len()];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([new_root.0]);
+
+        while let Some(oid) = queue.pop_front() {
+            remap[oid] = order.len();
+            order.push(oid);
+            for child in &old[oid].as_ref().unwrap().children {
+                queue.push_back(child.0);
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(order.len());
+        for oid in order {
+            let mut node = old[oid].take().unwrap();
+            node.parent = node
+                .parent
+                .map(|p| remap[p.0])
+                .filter(|&m| m != usize::MAX)
+                .map(NodeId);
+            node.children = node.children.iter().map(|c| NodeId(remap[c.0])).collect();
+            nodes.push(node);
+        }
+
+        self.nodes = nodes;
+        self.root = NodeId(0);
     }
 }
 
-trait GameState<U> {
+pub trait GameState<U> {
     fn get_actions(&self) -> Vec<U>;
     fn get_next_state(&self, action: U) -> Self;
     fn is_terminal(&self) -> Option<f32>;
+
+    /// Prior weight for each legal action, used by prior-aware policies such as
+    /// PUCT and to order progressive widening. Defaults to a uniform
+    /// distribution over [`get_actions`]; override with a heuristic or a policy
+    /// network where one is available.
+    fn action_priors(&self) -> Vec<(U, f32)> {
+        let actions = self.get_actions();
+        let prior = if actions.is_empty() {
+            0.0
+        } else {
+            1.0 / actions.len() as f32
+        };
+        actions.into_iter().map(|action| (action, prior)).collect()
+    }
+}
+
+/// How [`MCTS::select`] scores a child against its siblings. Higher scores win;
+/// scores are compared with [`f32::total_cmp`].
+pub trait SelectionPolicy {
+    /// Score a child given the parent's visit count, the child's own stats, its
+    /// edge prior, and the configured exploration constant.
+    fn score(
+        &self,
+        parent_visits: u32,
+        child_visits: u32,
+        child_value: f32,
+        prior: f32,
+        exploration: f32,
+    ) -> f32;
+}
+
+/// Classic UCB1: `avg_value + c * sqrt(2 * ln(parent_visits) / child_visits)`.
+/// Unvisited children score `+∞` so they are tried before any visited sibling.
+pub struct Ucb1;
+
+impl SelectionPolicy for Ucb1 {
+    fn score(
+        &self,
+        parent_visits: u32,
+        child_visits: u32,
+        child_value: f32,
+        _prior: f32,
+        exploration: f32,
+    ) -> f32 {
+        if child_visits == 0 {
+            return f32::INFINITY;
+        }
+
+        child_value / child_visits as f32
+            + exploration * (2.0 * (parent_visits as f32).ln() / child_visits as f32).sqrt()
+    }
+}
+
+/// PUCT: `q + c * prior * sqrt(parent_visits) / (1 + child_visits)`, the
+/// prior-weighted selection rule used by AlphaZero-style searches.
+pub struct Puct;
+
+impl SelectionPolicy for Puct {
+    fn score(
+        &self,
+        parent_visits: u32,
+        child_visits: u32,
+        child_value: f32,
+        prior: f32,
+        exploration: f32,
+    ) -> f32 {
+        let q = if child_visits == 0 {
+            0.0
+        } else {
+            child_value / child_visits as f32
+        };
+
+        q + exploration * prior * (parent_visits as f32).sqrt() / (1.0 + child_visits as f32)
+    }
 }
 
-struct MCTS<T: GameState<U>, U> {
+/// Play a uniformly random game to termination and return its value.
+///
+/// This is a free function (rather than a method) so it can run on a worker
+/// thread with its own `rng`, independent of the owning [`MCTS`].
+fn rollout<T, U, R>(state: &T, rng: &mut R) -> f32
+where
+    T: GameState<U> + Clone,
+    U: Copy,
+    R: Rng,
+{
+    let mut state = state.clone();
+
+    while state.is_terminal().is_none() {
+        let actions = state.get_actions();
+        let action = actions[rng.gen_range(0..actions.len())];
+        state = state.get_next_state(action);
+    }
+
+    state.is_terminal().unwrap()
+}
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct MCTS<T: GameState<U>, U, R: Rng = StdRng> {
     tree: Tree<T, U>,
+    rng: R,
+    virtual_loss: u32,
+    policy: Box<dyn SelectionPolicy>,
+    exploration: f32,
+    /// Progressive-widening `(k, alpha)`: a node exposes at most
+    /// `floor(k * visits^alpha)` children. `None` expands every action at once.
+    widening: Option<(f32, f32)>,
 }
 
-impl<T, U> MCTS<T, U>
+/// Default exploration constant. `1.0` reproduces the original UCB1 weighting.
+const DEFAULT_EXPLORATION: f32 = 1.0;
+
+impl<T, U> MCTS<T, U, StdRng>
 where
     T: GameState<U> + Clone + Debug,
     U: Copy + Debug,
 {
-    fn new(state: T) -> Self {
+    pub fn new(state: T) -> Self {
         Self {
             tree: Tree::new(state),
+            rng: StdRng::from_entropy(),
+            virtual_loss: DEFAULT_VIRTUAL_LOSS,
+            policy: Box::new(Ucb1),
+            exploration: DEFAULT_EXPLORATION,
+            widening: None,
         }
     }
 
-    fn search(&mut self, iterations: u32) {
+    /// Build a search seeded from a fixed `u64`, so rollouts and tie-breaking
+    /// replay identically run to run. Use this for deterministic tests, bug
+    /// reproduction, and comparing policies without rollout noise.
+    pub fn new_seeded(state: T, seed: u64) -> Self {
+        Self {
+            tree: Tree::new(state),
+            rng: StdRng::seed_from_u64(seed),
+            virtual_loss: DEFAULT_VIRTUAL_LOSS,
+            policy: Box::new(Ucb1),
+            exploration: DEFAULT_EXPLORATION,
+            widening: None,
+        }
+    }
+
+    /// Load a tree previously written by [`save`](Self::save) /
+    /// [`search_with_checkpoint`](Self::search_with_checkpoint), rebuilding an
+    /// `MCTS` around it with a fresh RNG and the default policy. Search can
+    /// continue warm from the restored statistics.
+    #[cfg(feature = "serde")]
+    pub fn load<Rd: std::io::Read>(r: Rd) -> std::io::Result<Self>
+    where
+        T: DeserializeOwned,
+        U: DeserializeOwned,
+    {
+        let tree: Tree<T, U> = serde_json::from_reader(r).map_err(std::io::Error::other)?;
+
+        Ok(Self {
+            tree,
+            rng: StdRng::from_entropy(),
+            virtual_loss: DEFAULT_VIRTUAL_LOSS,
+            policy: Box::new(Ucb1),
+            exploration: DEFAULT_EXPLORATION,
+            widening: None,
+        })
+    }
+}
+
+impl<T, U, R> MCTS<T, U, R>
+where
+    T: GameState<U> + Clone + Debug,
+    U: Copy + Debug,
+    R: Rng,
+{
+    /// Set the virtual-loss magnitude used by [`search_parallel`]: the number
+    /// of phantom visits temporarily charged to a leaf's path so concurrent
+    /// threads diverge to different branches. Larger values push threads apart
+    /// more aggressively.
+    pub fn with_virtual_loss(mut self, virtual_loss: u32) -> Self {
+        self.virtual_loss = virtual_loss;
+        self
+    }
+
+    /// Replace the selection policy (default [`Ucb1`]; [`Puct`] is also built
+    /// in). Custom policies only need to implement [`SelectionPolicy`].
+    pub fn with_policy(mut self, policy: Box<dyn SelectionPolicy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the exploration constant passed to the selection policy.
+    pub fn with_exploration(mut self, exploration: f32) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Enable progressive widening: a node exposes at most
+    /// `floor(k * visits^alpha)` of its actions (highest prior first), so
+    /// high-branching games do not materialise every child up front.
+    pub fn with_progressive_widening(mut self, k: f32, alpha: f32) -> Self {
+        self.widening = Some((k, alpha));
+        self
+    }
+
+    /// Serialize the full arena tree — node array, stats, states, and actions —
+    /// to `w` as JSON. Pairs with [`load`](Self::load) to round-trip a search.
+    #[cfg(feature = "serde")]
+    pub fn save<W: std::io::Write>(&self, w: W) -> std::io::Result<()>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        serde_json::to_writer(w, &self.tree).map_err(std::io::Error::other)
+    }
+
+    /// Run `iterations` of search, durably checkpointing the tree to `path`
+    /// every `every` iterations. Each checkpoint is written to a temporary file
+    /// and then renamed over `path`, so an interrupted run leaves either the
+    /// previous snapshot or the new one intact — never a half-written file. Use
+    /// [`load`](Self::load) on `path` to resume from the last snapshot.
+    #[cfg(feature = "serde")]
+    pub fn search_with_checkpoint(
+        &mut self,
+        iterations: u32,
+        every: u32,
+        path: impl AsRef<std::path::Path>,
+    ) -> std::io::Result<()>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        let every = every.max(1);
+        let mut done = 0;
+
+        while done < iterations {
+            let chunk = every.min(iterations - done);
+            self.search(chunk);
+            done += chunk;
+            self.checkpoint(path.as_ref())?;
+        }
+
+        Ok(())
+    }
+
+    /// Durably write the current tree to `path`: serialize to a temporary file,
+    /// `fsync` it, rename it over `path`, then `fsync` the containing directory
+    /// so the rename itself survives a crash. After a power loss either the
+    /// previous snapshot or this one is present and complete — never a
+    /// half-written or absent file.
+    #[cfg(feature = "serde")]
+    fn checkpoint(&self, path: &std::path::Path) -> std::io::Result<()>
+    where
+        T: Serialize,
+        U: Serialize,
+    {
+        use std::io::Write;
+
+        let tmp = path.with_extension("tmp");
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&tmp)?);
+        self.save(&mut writer)?;
+        writer.flush()?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        drop(file);
+
+        std::fs::rename(&tmp, path)?;
+
+        // fsync the directory so the rename entry is durable, not just visible.
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => std::path::Path::new("."),
+        };
+        std::fs::File::open(dir)?.sync_all()?;
+
+        Ok(())
+    }
+
+    pub fn search(&mut self, iterations: u32) {
         for _ in 0..iterations {
-            let mut node = self.tree.root.clone();
-            let mut state = node.borrow().state.clone();
+            let leaf = self.select_leaf();
+            let value = self.simulate(&self.tree.node(leaf).state.clone());
+            self.backpropagate(leaf, value);
+        }
+    }
 
-            while !node.borrow().children.is_empty() && !state.is_terminal().is_some() {
-                node = self.select(&node);
-                state = node.borrow().state.clone();
+    /// Leaf-parallel search: select a batch of up to `threads` leaves (applying
+    /// virtual loss so they tend to diverge; the same leaf may still be picked
+    /// twice), undo the virtual loss, run their rollouts on a rayon thread
+    /// pool, then backpropagate the real values. Selection and expansion stay sequential — only the independent
+    /// rollouts, which read a cloned state, cross thread boundaries.
+    pub fn search_parallel(&mut self, iterations: u32, threads: usize)
+    where
+        T: Send + Sync,
+        U: Send,
+    {
+        let threads = threads.max(1);
+        let mut remaining = iterations;
+
+        while remaining > 0 {
+            let batch = (threads as u32).min(remaining) as usize;
+
+            // select the batch, charging virtual loss along each path so the
+            // next selection avoids branches already claimed this round.
+            let mut jobs: Vec<(NodeId, T, u64)> = Vec::with_capacity(batch);
+            for _ in 0..batch {
+                let leaf = self.select_leaf();
+                self.apply_virtual_loss(leaf);
+                let state = self.tree.node(leaf).state.clone();
+                jobs.push((leaf, state, self.rng.gen()));
             }
 
-            if let Some(value) = state.is_terminal() {
-                self.backpropagate(&node, value);
-            } else {
-                self.expand(&node);
-                let node = self.select(&node);
-                let value = self.simulate(&node.borrow().state);
-                self.backpropagate(&node, value);
+            // undo the phantom visits before the real backpropagation.
+            for &(leaf, _, _) in &jobs {
+                self.remove_virtual_loss(leaf);
             }
+
+            let results: Vec<(NodeId, f32)> = jobs
+                .into_par_iter()
+                .map(|(leaf, state, seed)| {
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    (leaf, rollout::<T, U, StdRng>(&state, &mut rng))
+                })
+                .collect();
+
+            for (leaf, value) in results {
+                self.backpropagate(leaf, value);
+            }
+
+            remaining -= batch as u32;
         }
     }
 
-    fn expand(&mut self, node: &Link<T, U>) {
-        let state = node.borrow().state.clone();
+    /// Descend from the root to a leaf, expanding it if it is non-terminal and
+    /// unexpanded, and return the node to roll out from.
+    fn select_leaf(&mut self) -> NodeId {
+        let mut node = self.tree.root;
+
+        loop {
+            if self.tree.node(node).state.is_terminal().is_some() {
+                return node;
+            }
+
+            if self.tree.node(node).children.is_empty() {
+                self.expand(node);
+                return self.select(node);
+            }
 
-        for action in state.get_actions() {
-            let next_state = state.get_next_state(action);
-            self.tree.add_child(node, next_state, action);
+            self.maybe_widen(node);
+            node = self.select(node);
         }
     }
 
-    fn select(&self, node: &Link<T, U>) -> Link<T, U> {
-        node.borrow()
-            .children
-            .iter()
-            .max_by_key(|child| self.ucb1(child) as i32)
-            .unwrap()
-            .clone()
+    /// When progressive widening is enabled, turn more of a node's pending
+    /// actions into children up to the `floor(k * visits^alpha)` budget (at
+    /// least one). A no-op when widening is disabled or every action is already
+    /// expanded.
+    fn maybe_widen(&mut self, node: NodeId) {
+        let Some((k, alpha)) = self.widening else {
+            return;
+        };
+
+        let visits = self.tree.node(node).stats.visits;
+        let allowed = (k * (visits as f32).powf(alpha)).floor().max(1.0) as usize;
+
+        while self.tree.node(node).children.len() < allowed
+            && !self.tree.node(node).unexpanded.is_empty()
+        {
+            let (action, prior) = self.tree.node_mut(node).unexpanded.remove(0);
+            let next_state = self.tree.node(node).state.get_next_state(action);
+            self.tree.add_child(node, next_state, action, prior);
+        }
     }
 
-    fn ucb1(&self, node: &Link<T, U>) -> f32 {
-        let parent_visits = node.borrow().parent.clone().unwrap().borrow().stats.visits;
-        let node_visits = node.borrow().stats.visits;
-        let node_value = node.borrow().stats.value;
-
-        // avg_value + sqrt(2 * ln(parent_visits) / node_visits)
-        node_value / node_visits as f32
-            + (2.0 * (parent_visits as f32).ln() / node_visits as f32).sqrt()
+    /// Charge `virtual_loss` phantom *losing* visits to every node on the path
+    /// from `node` to the root: each gains `virtual_loss` visits with a
+    /// pessimistic value of `-1` apiece (its total value drops by
+    /// `virtual_loss`), so the running average drops and concurrent
+    /// selections in the same batch are steered toward other branches. Undone
+    /// by [`remove_virtual_loss`] before the real value is backpropagated.
+    fn apply_virtual_loss(&mut self, node: NodeId) {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            let stats = &mut self.tree.node_mut(id).stats;
+            stats.visits += self.virtual_loss;
+            stats.value -= self.virtual_loss as f32;
+            current = self.tree.node(id).parent;
+        }
     }
 
-    fn simulate(&self, state: &T) -> f32 {
-        let mut state = state.clone();
+    fn remove_virtual_loss(&mut self, node: NodeId) {
+        let mut current = Some(node);
+        while let Some(id) = current {
+            let stats = &mut self.tree.node_mut(id).stats;
+            stats.visits -= self.virtual_loss;
+            stats.value += self.virtual_loss as f32;
+            current = self.tree.node(id).parent;
+        }
+    }
 
-        while !state.is_terminal().is_some() {
-            let actions = state.get_actions();
-            let action = actions[rand::random::<usize>() % actions.len()];
-            state = state.get_next_state(action);
+    fn expand(&mut self, node: NodeId) {
+        let state = self.tree.node(node).state.clone();
+
+        // highest-prior actions first, so progressive widening reveals the most
+        // promising children earliest.
+        let mut priors = state.action_priors();
+        priors.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        if self.widening.is_some() {
+            self.tree.node_mut(node).unexpanded = priors;
+            self.maybe_widen(node);
+        } else {
+            for (action, prior) in priors {
+                let next_state = state.get_next_state(action);
+                self.tree.add_child(node, next_state, action, prior);
+            }
         }
+    }
 
-        state.is_terminal().unwrap()
+    fn select(&mut self, node: NodeId) -> NodeId {
+        let children = self.tree.node(node).children.clone();
+        let parent_visits = self.tree.node(node).stats.visits;
+
+        let best = children
+            .iter()
+            .map(|&child| self.score(parent_visits, child))
+            .max_by(f32::total_cmp);
+
+        // break ties between equally-scored children with an RNG draw so the
+        // search does not always walk the first-listed action.
+        let candidates: Vec<NodeId> = match best {
+            Some(best) => children
+                .into_iter()
+                .filter(|&child| self.score(parent_visits, child) == best)
+                .collect(),
+            None => children,
+        };
+
+        candidates[self.rng.gen_range(0..candidates.len())]
     }
 
-    fn backpropagate(&mut self, node: &Link<T, U>, value: f32) {
-        let mut current = node.clone();
+    fn score(&self, parent_visits: u32, child: NodeId) -> f32 {
+        let child = self.tree.node(child);
+        self.policy.score(
+            parent_visits,
+            child.stats.visits,
+            child.stats.value,
+            child.prior,
+            self.exploration,
+        )
+    }
 
-        loop {
-            current.borrow_mut().stats.visits += 1;
-            current.borrow_mut().stats.value += value;
+    fn simulate(&mut self, state: &T) -> f32 {
+        rollout::<T, U, R>(state, &mut self.rng)
+    }
+
+    fn backpropagate(&mut self, node: NodeId, value: f32) {
+        let mut current = Some(node);
 
-            let parent = current.borrow().parent.clone();
+        while let Some(id) = current {
+            let stats = &mut self.tree.node_mut(id).stats;
+            stats.visits += 1;
+            stats.value += value;
 
-            match parent {
-                Some(parent) => current = parent,
-                None => break,
+            current = self.tree.node(id).parent;
+        }
+    }
+
+    /// Advance the search to the state reached by playing `action`. When that
+    /// action already has an expanded root child, it becomes the new root —
+    /// its siblings and their subtrees are discarded and its accumulated
+    /// `visits`/`value` carry over so the next `search` continues warm, and
+    /// `true` is returned.
+    ///
+    /// When the action has no expanded root child — because the root was never
+    /// searched, or progressive widening has not yet revealed it — the tree is
+    /// reset to a fresh root at `get_next_state(action)` and `false` is
+    /// returned. A legal move therefore never panics; it just starts the next
+    /// search cold.
+    pub fn advance_root(&mut self, action: U) -> bool
+    where
+        U: PartialEq,
+    {
+        let root = self.tree.root;
+        let child = self
+            .tree
+            .node(root)
+            .children
+            .iter()
+            .copied()
+            .find(|&child| self.tree.node(child).action == Some(action));
+
+        match child {
+            Some(child) => {
+                self.tree.reroot(child);
+                true
+            }
+            None => {
+                let next_state = self.tree.node(root).state.get_next_state(action);
+                self.tree = Tree::new(next_state);
+                false
             }
         }
     }
 
-    fn get_principal_variation(&self) -> Vec<U> {
-        let mut node = self.tree.root.clone();
+    pub fn get_principal_variation(&self) -> Vec<U> {
+        let mut node = self.tree.root;
         let mut actions = Vec::new();
 
         loop {
-            let mut child = node.borrow().children.clone();
-            let child = child.iter().max_by_key(|child| child.borrow().stats.visits);
-
-            if let Some(child) = child {
-                actions.push(child.borrow().action.unwrap());
-                node = child.clone();
+            let child = self
+                .tree
+                .node(node)
+                .children
+                .iter()
+                .max_by_key(|&&child| self.tree.node(child).stats.visits);
+
+            if let Some(&child) = child {
+                actions.push(self.tree.node(child).action.unwrap());
+                node = child;
             } else {
                 break;
             }
@@ -183,10 +660,12 @@ where
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
     #[derive(Debug, Clone)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     struct NimState {
         heap: u32,
     }
@@ -208,7 +687,7 @@ mod tests {
         }
 
         fn is_terminal(&self) -> Option<f32> {
-            if self.heap <= 0 {
+            if self.heap == 0 {
                 Some(1.0)
             } else {
                 None
@@ -219,29 +698,143 @@ mod tests {
     #[test]
     fn test_tree() {
         let mut tree = Tree::new(0);
-        let root = tree.root.clone();
-        tree.add_child(&root, 1, 1);
-        tree.add_child(&root, 2, 2);
-        let root = tree.root.borrow();
+        let root = tree.root;
+        tree.add_child(root, 1, 1, 0.5);
+        tree.add_child(root, 2, 2, 0.5);
+        let root = tree.node(tree.root);
 
         assert_eq!(root.children.len(), 2);
-        assert_eq!(root.children[0].borrow().state, 1);
-        assert_eq!(root.children[1].borrow().state, 2);
+        assert_eq!(tree.node(root.children[0]).state, 1);
+        assert_eq!(tree.node(root.children[1]).state, 2);
     }
 
     #[test]
     fn test_mcts() {
-        // this test is not deterministic, but more for a sanity/visual check
-
-        let mut mcts = MCTS::new(NimState::new(100));
+        // a fixed seed makes this run reproducible instead of leaning on the
+        // global thread RNG.
+        let mut mcts = MCTS::new_seeded(NimState::new(100), 0);
         mcts.search(1);
 
-        for child in mcts.tree.root.borrow().children.iter() {
-            println!("{:?}", child.borrow().stats);
+        for &child in mcts.tree.node(mcts.tree.root).children.iter() {
+            println!("{:?}", mcts.tree.node(child).stats);
         }
 
         mcts.search(1000);
 
         println!("Principal variation: {:?}", mcts.get_principal_variation());
     }
+
+    #[test]
+    fn test_search_parallel_conserves_visits() {
+        let iterations = 600;
+
+        let mut sequential = MCTS::new_seeded(NimState::new(50), 0);
+        sequential.search(iterations);
+
+        let mut parallel = MCTS::new_seeded(NimState::new(50), 0);
+        parallel.search_parallel(iterations, 4);
+
+        // every iteration backpropagates exactly one visit up to the root, so
+        // both schedules must leave the root with the same visit total — and
+        // the virtual-loss apply/undo must net to zero (no underflow). The
+        // rollouts themselves draw from the RNG in a different order under the
+        // parallel schedule, so only the visit bookkeeping is expected to
+        // coincide, not the per-node values.
+        for mcts in [&sequential, &parallel] {
+            let root = mcts.tree.node(mcts.tree.root);
+            assert_eq!(root.stats.visits, iterations);
+
+            // visits are conserved down the tree too: every iteration descends
+            // through exactly one root child, so the children's visits sum back
+            // to the root's total.
+            let child_visits: u32 = root
+                .children
+                .iter()
+                .map(|&child| mcts.tree.node(child).stats.visits)
+                .sum();
+            assert_eq!(child_visits, iterations);
+        }
+    }
+
+    #[test]
+    fn test_advance_root() {
+        let mut mcts = MCTS::new_seeded(NimState::new(100), 0);
+        mcts.search(1000);
+
+        // remember the stats of the subtree reached by taking action 3.
+        let child = *mcts
+            .tree
+            .node(mcts.tree.root)
+            .children
+            .iter()
+            .find(|&&c| mcts.tree.node(c).action == Some(3))
+            .unwrap();
+        let visits = mcts.tree.node(child).stats.visits;
+        let grandchildren = mcts.tree.node(child).children.len();
+
+        assert!(mcts.advance_root(3));
+
+        let root = mcts.tree.node(mcts.tree.root);
+        assert!(root.parent.is_none());
+        assert_eq!(root.action, Some(3));
+        assert_eq!(root.stats.visits, visits);
+        assert_eq!(root.children.len(), grandchildren);
+    }
+
+    #[test]
+    fn test_advance_root_unsearched_falls_back() {
+        // advancing before any search must not panic on a legal move; it resets
+        // to a fresh cold root at the resulting state.
+        let mut mcts = MCTS::new_seeded(NimState::new(100), 0);
+
+        assert!(!mcts.advance_root(7));
+
+        let root = mcts.tree.node(mcts.tree.root);
+        assert!(root.parent.is_none());
+        assert_eq!(root.state.heap, 93);
+        assert_eq!(root.stats.visits, 0);
+    }
+
+    #[test]
+    fn test_puct_with_widening() {
+        // PUCT plus progressive widening should still run to a principal
+        // variation, and widening must keep the root child count within the
+        // floor(k * visits^alpha) budget.
+        let k = 2.0;
+        let alpha = 0.5;
+        let mut mcts = MCTS::new_seeded(NimState::new(100), 0)
+            .with_policy(Box::new(Puct))
+            .with_exploration(1.5)
+            .with_progressive_widening(k, alpha);
+        mcts.search(1000);
+
+        let root = mcts.tree.node(mcts.tree.root);
+        let allowed = (k * (root.stats.visits as f32).powf(alpha)).floor().max(1.0) as usize;
+        assert!(root.children.len() <= allowed);
+
+        assert!(!mcts.get_principal_variation().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_load_roundtrip() {
+        let mut mcts = MCTS::new_seeded(NimState::new(100), 0);
+        mcts.search(500);
+
+        let mut buf = Vec::new();
+        mcts.save(&mut buf).unwrap();
+
+        let restored: MCTS<NimState, u32> = MCTS::load(&buf[..]).unwrap();
+
+        // the restored tree must carry the accumulated statistics verbatim.
+        assert_eq!(restored.tree.nodes.len(), mcts.tree.nodes.len());
+        assert_eq!(
+            restored.tree.node(restored.tree.root).stats.visits,
+            mcts.tree.node(mcts.tree.root).stats.visits
+        );
+        assert_eq!(
+            restored.get_principal_variation(),
+            mcts.get_principal_variation()
+        );
+    }
 }